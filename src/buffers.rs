@@ -0,0 +1,88 @@
+//! Reusable buffers for spanning-tree sampling.
+use rand::Rng;
+
+/// A reusable buffer for a spanning tree (or, for a disconnected graph, a
+/// spanning forest) sampled from a graph.
+pub struct SpanningTreeBuffer {
+    /// Adjacency lists of the sampled spanning tree (or forest), indexed by node.
+    pub st: Vec<Vec<usize>>,
+    /// One representative node per connected component of the sampled
+    /// forest (a single entry when the graph is connected).
+    pub roots: Vec<usize>,
+}
+
+impl SpanningTreeBuffer {
+    /// Creates a buffer for a spanning tree (or forest) of a graph of size `n`.
+    pub fn new(n: usize) -> SpanningTreeBuffer {
+        SpanningTreeBuffer {
+            st: vec![Vec::new(); n],
+            roots: Vec::new(),
+        }
+    }
+
+    /// Resets the buffer.
+    pub fn clear(&mut self) {
+        for neighbors in self.st.iter_mut() {
+            neighbors.clear();
+        }
+        self.roots.clear();
+    }
+}
+
+/// The number of `u32` words kept in a `RandomRangeBuffer`'s reservoir.
+/// Refilled in one batched `rng.fill()` call once exhausted, to amortize the
+/// per-call cost of drawing from `rng`.
+const RESERVOIR_SIZE: usize = 256;
+
+/// A reusable reservoir of random `u32` words, used to quickly draw unbiased
+/// bounded integers via Lemire's method.
+pub struct RandomRangeBuffer {
+    reservoir: Vec<u32>,
+    pos: usize,
+}
+
+impl RandomRangeBuffer {
+    /// Creates a range buffer, filling its initial reservoir of random words
+    /// using `rng`.
+    pub fn new<R: Rng>(rng: &mut R) -> RandomRangeBuffer {
+        let mut reservoir = vec![0u32; RESERVOIR_SIZE];
+        rng.fill(&mut reservoir[..]);
+        RandomRangeBuffer { reservoir, pos: 0 }
+    }
+
+    /// Draws a value uniformly at random from `[0, n)` using Lemire's method:
+    /// draw a random `u32` word `x`, compute the 64-bit product
+    /// `m = (x as u64) * (n as u64)`, and take `hi = (m >> 32) as u32` as the
+    /// result; if the low half `lo = m as u32` is below the rejection
+    /// threshold `t = (u32::MAX - n + 1) % n` (computed once per call),
+    /// reject and redraw. Each call costs one modulo (for the threshold) plus
+    /// one multiply per draw, with a redraw only on rejection, and there is
+    /// no cap on `n` (unlike the old byte-sized reservoir it replaced).
+    ///
+    /// # Panics
+    /// Panics if `n == 0`.
+    pub fn range<R: Rng>(&mut self, rng: &mut R, n: u32) -> u32 {
+        assert!(n > 0, "range upper bound must be positive");
+        let threshold = (u32::MAX - n + 1) % n;
+        loop {
+            let x = self.next_word(rng);
+            let m = (x as u64) * (n as u64);
+            let lo = m as u32;
+            if lo >= threshold {
+                return (m >> 32) as u32;
+            }
+        }
+    }
+
+    /// Returns the next random word from the reservoir, refilling it from
+    /// `rng` (in one batched call) once exhausted.
+    fn next_word<R: Rng>(&mut self, rng: &mut R) -> u32 {
+        if self.pos >= self.reservoir.len() {
+            rng.fill(&mut self.reservoir[..]);
+            self.pos = 0;
+        }
+        let x = self.reservoir[self.pos];
+        self.pos += 1;
+        x
+    }
+}
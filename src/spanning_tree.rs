@@ -1,21 +1,32 @@
 //! Functions for generating random spanning trees.
+//!
+//! All three samplers in this module (`USTSampler`, `WeightedUSTSampler`, and
+//! `RMSTSampler`) accept a disconnected `graph` (e.g. a subgraph induced by
+//! splitting a district) and fall back to sampling a spanning *forest*
+//! instead of panicking or hanging: one tree per connected component, with
+//! one representative node per component recorded in `buf.roots`.
 use crate::buffers::SpanningTreeBuffer;
 use crate::graph::{Edge, Graph};
-use rand::rngs::SmallRng;
 use rand::Rng;
 use std::cmp::{max, min};
 
 pub trait SpanningTreeSampler {
     /// Samples a random tree of `graph` using `rng`; inserts the tree into `buf`.
-    fn random_spanning_tree(
+    ///
+    /// Generic over the RNG (`R: Rng`) rather than hardcoding a particular
+    /// generator, so callers that need bit-for-bit reproducible ensembles
+    /// (e.g. for audit or publication) can plug in a seeded generator such as
+    /// `rand_chacha::ChaCha20Rng`, while callers that only care about speed
+    /// can keep using `rand::rngs::SmallRng`.
+    fn random_spanning_tree<R: Rng>(
         &mut self,
         graph: &Graph,
         buf: &mut SpanningTreeBuffer,
-        rng: &mut SmallRng,
+        rng: &mut R,
     );
 }
 pub use crate::spanning_tree::rmst::RMSTSampler;
-pub use crate::spanning_tree::ust::USTSampler;
+pub use crate::spanning_tree::ust::{USTSampler, WeightedUSTSampler};
 
 /// Spanning tree sampling from the uniform distribution.
 mod ust {
@@ -30,6 +41,11 @@ mod ust {
         pub next: Vec<i64>,
         /// The edges in the MST.
         pub edges: Vec<usize>,
+        /// Scratch space for discovering connected components when seeding
+        /// spanning-forest roots (see `USTSampler::random_spanning_tree`).
+        component_seen: Vec<bool>,
+        /// Scratch stack reused for the connected-component walk.
+        component_stack: Vec<usize>,
     }
 
     impl USTBuffer {
@@ -40,6 +56,8 @@ mod ust {
                 in_tree: vec![false; n],
                 next: vec![-1 as i64; n],
                 edges: Vec::<usize>::with_capacity(n - 1),
+                component_seen: vec![false; n],
+                component_stack: Vec::new(),
             };
         }
 
@@ -48,6 +66,31 @@ mod ust {
             self.in_tree.fill(false);
             self.next.fill(-1);
             self.edges.clear();
+            self.component_seen.fill(false);
+            self.component_stack.clear();
+        }
+
+        /// Reconstructs spanning-tree/-forest edges into `self.edges` from
+        /// `self.next` (as built by a loop-erased random walk, weighted or
+        /// not): for each `curr` with `next[curr] = prev >= 0`, looks up the
+        /// `(curr, prev)` edge in `graph.edges`. Shared by `USTSampler` and
+        /// `WeightedUSTSampler`, whose walks differ only in how they pick
+        /// the next neighbor.
+        pub fn reconstruct_edges(&mut self, graph: &Graph) {
+            for (curr, &prev) in self.next.iter().enumerate() {
+                if prev >= 0 {
+                    let a = min(curr, prev as usize);
+                    let b = max(curr, prev as usize);
+                    let mut edge_idx = graph.edges_start[a];
+                    while graph.edges[edge_idx].0 == a {
+                        if graph.edges[edge_idx].1 == b {
+                            self.edges.push(edge_idx);
+                            break;
+                        }
+                        edge_idx += 1;
+                    }
+                }
+            }
         }
     }
 
@@ -55,14 +98,16 @@ mod ust {
     pub struct USTSampler {
         /// A buffer for Wilson's algorithm.
         ust_buf: USTBuffer,
-        /// A reservoir of random bytes (used for quickly selecting random node neighbors).
+        /// A reservoir of random `u32` words, used to quickly draw unbiased
+        /// bounded integers (via Lemire's method) for selecting random node
+        /// neighbors.
         range_buf: RandomRangeBuffer,
     }
 
     impl USTSampler {
         /// Creates a UST sampler (and underlying buffers) for a graph of approximate
-        /// size `n`. (A reservoir of random bytes is initialized using `rng`.)
-        pub fn new(n: usize, rng: &mut SmallRng) -> USTSampler {
+        /// size `n`. (A reservoir of random words is initialized using `rng`.)
+        pub fn new<R: Rng>(n: usize, rng: &mut R) -> USTSampler {
             USTSampler {
                 ust_buf: USTBuffer::new(n),
                 range_buf: RandomRangeBuffer::new(rng),
@@ -70,42 +115,255 @@ mod ust {
         }
     }
 
+    /// Samples random spanning trees with probability proportional to the
+    /// product of their edge weights.
+    pub struct WeightedUSTSampler {
+        /// A buffer for Wilson's algorithm.
+        ust_buf: USTBuffer,
+        /// Per-node cumulative (prefix-sum) edge weights, aligned with
+        /// `graph.neighbors[u]`.
+        cum_weights: Vec<Vec<f64>>,
+    }
+
+    impl WeightedUSTSampler {
+        /// Creates a weighted UST sampler (and underlying buffers) for a graph
+        /// of approximate size `n`.
+        pub fn new(n: usize) -> WeightedUSTSampler {
+            WeightedUSTSampler {
+                ust_buf: USTBuffer::new(n),
+                cum_weights: Vec::new(),
+            }
+        }
+
+        /// Recomputes the per-node cumulative edge weights from `weights`
+        /// (one entry per `graph.edges`, aligned by index). Must be called
+        /// whenever the graph or edge weights change.
+        ///
+        /// Reuses each node's inner `Vec<f64>` across calls (`clear()` keeps
+        /// its allocation) instead of reallocating `graph.neighbors.len()`
+        /// fresh vectors every sample, matching the buffer-reuse discipline
+        /// `RMSTSampler` uses elsewhere in this module.
+        ///
+        /// # Panics
+        /// Panics if any edge incident to a node has a negative weight,
+        /// since a running sum over a negative weight makes `cum_weights[u]`
+        /// non-monotonic, which breaks the binary search in
+        /// `weighted_neighbor`.
+        fn update_weights(&mut self, graph: &Graph, weights: &[f64]) {
+            self.cum_weights.resize_with(graph.neighbors.len(), Vec::new);
+            for (u, neighbors) in graph.neighbors.iter().enumerate() {
+                let cum = &mut self.cum_weights[u];
+                cum.clear();
+                cum.reserve(neighbors.len());
+                let mut total = 0.0;
+                for &v in neighbors.iter() {
+                    let w = Self::edge_weight(graph, weights, u, v);
+                    assert!(
+                        w >= 0.0,
+                        "edge ({}, {}) has negative weight {} (negative edge weights are not supported)",
+                        u,
+                        v,
+                        w
+                    );
+                    total += w;
+                    cum.push(total);
+                }
+            }
+        }
+
+        /// Looks up the weight of edge `(u, v)` in `graph.edges`.
+        fn edge_weight(graph: &Graph, weights: &[f64], u: usize, v: usize) -> f64 {
+            let a = min(u, v);
+            let b = max(u, v);
+            let mut edge_idx = graph.edges_start[a];
+            while graph.edges[edge_idx].0 == a {
+                if graph.edges[edge_idx].1 == b {
+                    return weights[edge_idx];
+                }
+                edge_idx += 1;
+            }
+            panic!("no edge found between nodes {} and {}", u, v);
+        }
+
+        /// Draws the next neighbor of `u` with probability proportional to
+        /// edge weight, by drawing a uniform value in `[0, total_weight(u))`
+        /// and binary-searching the cumulative-weight prefix sums.
+        ///
+        /// # Panics
+        /// Panics if the total incident weight of `u` is not positive (e.g.
+        /// all of `u`'s incident edges have weight `0.0`), since there is then
+        /// no well-defined probability-proportional-to-weight neighbor to draw.
+        fn weighted_neighbor<R: Rng>(&self, graph: &Graph, u: usize, rng: &mut R) -> usize {
+            let cum = &self.cum_weights[u];
+            let total = *cum.last().unwrap();
+            if total.is_nan() || total <= 0.0 {
+                panic!(
+                    "node {} has non-positive total incident edge weight {} \
+                     (all-zero or negative weights are not supported)",
+                    u, total
+                );
+            }
+            let target = rng.gen_range(0.0..total);
+            let idx = cum.partition_point(|&w| w <= target);
+            graph.neighbors[u][idx]
+        }
+
+        /// Draws a random spanning tree of `graph` with probability
+        /// proportional to the product of its edge weights, using the
+        /// `next_edge` generalization of Wilson's algorithm [1] (a weighted
+        /// loop-erased random walk). Returns nothing; the MST buffer `buf`
+        /// is updated in place.
+        ///
+        /// # Arguments
+        /// * `graph` - The graph to form a spanning tree from.
+        /// * `weights` - Edge weights, one per `graph.edges` entry (aligned
+        ///   by index). The next neighbor of `u` in the walk is chosen with
+        ///   probability proportional to the weight of edge `(u, v)`.
+        /// * `buf` - The buffer to insert the spanning tree into.
+        /// * `rng` - A random number generator (used to draw each step of the
+        ///   walk; the component roots are chosen deterministically, not
+        ///   drawn from `rng`).
+        ///
+        /// # References
+        /// [1]  Wilson, David Bruce. "Generating random spanning trees more
+        ///      quickly than the cover time." Proceedings of the twenty-eighth
+        ///      annual ACM symposium on Theory of computing. 1996.
+        pub fn random_spanning_tree<R: Rng>(
+            &mut self,
+            graph: &Graph,
+            weights: &[f64],
+            buf: &mut SpanningTreeBuffer,
+            rng: &mut R,
+        ) {
+            buf.clear();
+            self.ust_buf.clear();
+            self.update_weights(graph, weights);
+            let n = graph.pops.len();
+
+            // Seed one root per connected component, exactly like
+            // `USTSampler::random_spanning_tree`, so that the weighted walk
+            // below is always guaranteed to terminate -- including for a
+            // disconnected subgraph (e.g. a split district), which is the
+            // scenario this sampler is meant to support.
+            buf.roots.clear();
+            for start in 0..n {
+                if self.ust_buf.component_seen[start] {
+                    continue;
+                }
+                buf.roots.push(start);
+                self.ust_buf.in_tree[start] = true;
+                self.ust_buf.component_seen[start] = true;
+                self.ust_buf.component_stack.push(start);
+                while let Some(u) = self.ust_buf.component_stack.pop() {
+                    for &v in graph.neighbors[u].iter() {
+                        if !self.ust_buf.component_seen[v] {
+                            self.ust_buf.component_seen[v] = true;
+                            self.ust_buf.component_stack.push(v);
+                        }
+                    }
+                }
+            }
+
+            for i in 0..n {
+                let mut u = i;
+                while !self.ust_buf.in_tree[u] {
+                    let neighbor = self.weighted_neighbor(graph, u, rng);
+                    self.ust_buf.next[u] = neighbor as i64;
+                    u = neighbor;
+                }
+                u = i;
+                while !self.ust_buf.in_tree[u] {
+                    self.ust_buf.in_tree[u] = true;
+                    u = self.ust_buf.next[u] as usize;
+                }
+            }
+
+            self.ust_buf.reconstruct_edges(graph);
+            let expected_edges = n - buf.roots.len();
+            if self.ust_buf.edges.len() != expected_edges {
+                panic!(
+                    "expected to have {} edges in spanning forest but got {}",
+                    expected_edges,
+                    self.ust_buf.edges.len()
+                );
+            }
+
+            for &edge in self.ust_buf.edges.iter() {
+                let Edge(src, dst) = graph.edges[edge];
+                buf.st[src].push(dst);
+                buf.st[dst].push(src);
+            }
+        }
+    }
+
     impl SpanningTreeSampler for USTSampler {
-        /// Draws a random spanning tree of a graph from the uniform distribution.
+        /// Draws a random spanning tree (or, if `graph` is disconnected, a random
+        /// spanning forest) of a graph from the uniform distribution.
         /// Returns nothing; The MST buffer `buf` is updated in place.
         ///
         /// We use Wilson's algorithm [1] (which is, in essence, a self-avoiding random
         /// walk) to generate the tree.
         ///
+        /// `graph` need not be connected: we first find one root per connected
+        /// component (via a plain DFS over `graph.neighbors`) and seed each as
+        /// already "in tree" before running Wilson's walk, so the walk is always
+        /// guaranteed to terminate and the result is a spanning forest rather than
+        /// a single tree. The component roots are recorded in `buf.roots`.
+        ///
         /// # Arguments
-        /// * `graph` - The graph to form a spanning tree from. The maximum degree
-        ///   of the graph must be ≤256; otherwise, sampling from the uniform
-        ///   distribution is not guaranteed.
+        /// * `graph` - The graph to form a spanning tree from. There is no
+        ///   maximum-degree restriction: node neighbors are drawn using an
+        ///   unbiased bounded-integer sampler (Lemire's method), not a
+        ///   byte-sized reservoir.
         /// * `buf` - The buffer to insert the spanning tree into.
         /// * `rng` - A random number generator (used to select the spanning tree
-        ///   root and refresh the random byte reservoir).
+        ///   edges by refreshing the random word reservoir that each walk step
+        ///   draws from; the component roots are chosen deterministically, not
+        ///   drawn from `rng`).
         ///
         /// # References
         /// [1]  Wilson, David Bruce. "Generating random spanning trees more quickly
         ///      than the cover time." Proceedings of the twenty-eighth annual ACM
         ///      symposium on Theory of computing. 1996.
-        fn random_spanning_tree(
+        fn random_spanning_tree<R: Rng>(
             &mut self,
             graph: &Graph,
             buf: &mut SpanningTreeBuffer,
-            rng: &mut SmallRng,
+            rng: &mut R,
         ) {
             buf.clear();
             self.ust_buf.clear();
             let n = graph.pops.len();
-            let root = rng.gen_range(0..n);
-            self.ust_buf.in_tree[root] = true;
+
+            // Seed one root per connected component so that Wilson's walk is
+            // always guaranteed to terminate, even for a disconnected subgraph
+            // (e.g. a split district) -- this yields a spanning forest instead
+            // of looping forever waiting to hit a nonexistent in-tree node.
+            buf.roots.clear();
+            for start in 0..n {
+                if self.ust_buf.component_seen[start] {
+                    continue;
+                }
+                buf.roots.push(start);
+                self.ust_buf.in_tree[start] = true;
+                self.ust_buf.component_seen[start] = true;
+                self.ust_buf.component_stack.push(start);
+                while let Some(u) = self.ust_buf.component_stack.pop() {
+                    for &v in graph.neighbors[u].iter() {
+                        if !self.ust_buf.component_seen[v] {
+                            self.ust_buf.component_seen[v] = true;
+                            self.ust_buf.component_stack.push(v);
+                        }
+                    }
+                }
+            }
+
             for i in 0..n {
                 let mut u = i;
                 while !self.ust_buf.in_tree[u] {
                     let neighbors = &graph.neighbors[u];
                     let neighbor =
-                        neighbors[self.range_buf.range(rng, neighbors.len() as u8) as usize];
+                        neighbors[self.range_buf.range(rng, neighbors.len() as u32) as usize];
                     self.ust_buf.next[u] = neighbor as i64;
                     u = neighbor;
                 }
@@ -116,24 +374,12 @@ mod ust {
                 }
             }
 
-            for (curr, &prev) in self.ust_buf.next.iter().enumerate() {
-                if prev >= 0 {
-                    let a = min(curr, prev as usize);
-                    let b = max(curr, prev as usize);
-                    let mut edge_idx = graph.edges_start[a];
-                    while graph.edges[edge_idx].0 == a {
-                        if graph.edges[edge_idx].1 == b {
-                            self.ust_buf.edges.push(edge_idx);
-                            break;
-                        }
-                        edge_idx += 1;
-                    }
-                }
-            }
-            if self.ust_buf.edges.len() != n - 1 {
+            self.ust_buf.reconstruct_edges(graph);
+            let expected_edges = n - buf.roots.len();
+            if self.ust_buf.edges.len() != expected_edges {
                 panic!(
-                    "expected to have {} edges in MST but got {}",
-                    n - 1,
+                    "expected to have {} edges in spanning forest but got {}",
+                    expected_edges,
                     self.ust_buf.edges.len()
                 );
             }
@@ -145,6 +391,44 @@ mod ust {
             }
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use rand::rngs::SmallRng;
+        use rand::SeedableRng;
+
+        /// A 3-node star: node 0 is connected to both 1 and 2.
+        fn star_graph() -> Graph {
+            Graph {
+                pops: vec![1, 1, 1],
+                neighbors: vec![vec![1, 2], vec![0], vec![0]],
+                edges: vec![Edge(0, 1), Edge(0, 2)],
+                edges_start: vec![0, 2, 2],
+            }
+        }
+
+        #[test]
+        fn weighted_neighbor_prefers_heavier_edge() {
+            let graph = star_graph();
+            let weights = vec![1.0, 99.0];
+            let mut sampler = WeightedUSTSampler::new(graph.pops.len());
+            sampler.update_weights(&graph, &weights);
+
+            let mut rng = SmallRng::seed_from_u64(0);
+            let mut heavy_neighbor_count = 0;
+            let trials = 2000;
+            for _ in 0..trials {
+                if sampler.weighted_neighbor(&graph, 0, &mut rng) == 2 {
+                    heavy_neighbor_count += 1;
+                }
+            }
+
+            // Edge (0, 2) carries 99% of node 0's incident weight, so it
+            // should be picked the vast majority of the time.
+            assert!(heavy_neighbor_count > trials * 9 / 10);
+        }
+    }
 }
 
 /// Spanning tree sampling via random edge weights.
@@ -153,12 +437,27 @@ mod rmst {
     use ena::unify::{InPlace, UnificationTable, UnifyKey};
 
     type EdgeWeight = u32;
+    /// A graph edge paired with its (random) weight, as sorted by `radix_sort_by_weight`.
+    type WeightedEdge = (EdgeWeight, Edge);
 
     /// Samples random spanning trees by sampling random edge weights and finding
     /// the minimum spanning tree.
     pub struct RMSTSampler {
         /// Buffer for edge weights.
         weights: Vec<EdgeWeight>,
+        /// Reusable union-find table for Kruskal's algorithm; reset in place
+        /// between samples (via `reset_unifications`) rather than reallocated.
+        ut: UnificationTable<InPlace<NodeKey>>,
+        /// Reusable node keys into `ut`, one per node of the graph.
+        keys: Vec<NodeKey>,
+        /// Reusable buffer of (weight, edge) pairs, radix-sorted by weight.
+        edges_by_weight: Vec<WeightedEdge>,
+        /// Reusable scratch buffer for the LSB radix sort over `edges_by_weight`.
+        radix_scratch: Vec<WeightedEdge>,
+        /// Reusable "have we recorded this component's root yet" buffer,
+        /// indexed by root node id; reset in place between samples rather
+        /// than reallocated, mirroring `USTBuffer::component_seen`.
+        seen_roots: Vec<bool>,
     }
 
     impl RMSTSampler {
@@ -166,6 +465,11 @@ mod rmst {
         pub fn new(n: usize) -> RMSTSampler {
             RMSTSampler {
                 weights: Vec::<EdgeWeight>::with_capacity(8 * n),
+                ut: UnificationTable::new(),
+                keys: Vec::with_capacity(n),
+                edges_by_weight: Vec::with_capacity(8 * n),
+                radix_scratch: Vec::with_capacity(8 * n),
+                seen_roots: vec![false; n],
             }
         }
     }
@@ -188,71 +492,233 @@ mod rmst {
         }
     }
 
-    /// Given `weights`, finds the minimum spanning tree of `graph` using
-    /// Kruskal's algorithm and inserts the tree into `buf`.
-    fn minimum_spanning_tree(
-        graph: &Graph,
-        buf: &mut SpanningTreeBuffer,
-        weights: &Vec<EdgeWeight>,
+    /// Sorts `edges` in place by ascending `EdgeWeight` using an LSB radix
+    /// sort (four 8-bit passes over the `u32` weight), reusing `scratch` as
+    /// the second buffer instead of allocating one per call. Edge weights
+    /// are uniformly random `u32`s, so a counting sort per byte is both
+    /// correct and faster than a comparison sort.
+    fn radix_sort_by_weight(
+        edges: &mut Vec<WeightedEdge>,
+        scratch: &mut Vec<WeightedEdge>,
     ) {
-        buf.clear();
-
-        // Initialize a union-find data structure to keep track of connected
-        // components of the graph.
-        // TODO: buffer this?
-        let mut ut: UnificationTable<InPlace<NodeKey>> = UnificationTable::new();
-        ut.reserve(graph.edges.len());
-        let keys: Vec<NodeKey> = graph.edges.iter().map(|_| ut.new_key(())).collect();
-
-        // Apply Kruskal's algorithm: add edges until the graph is connected.
-        let mut edges_by_weight = weights
-            .iter()
-            .enumerate()
-            .map(|(idx, &w)| (w, graph.edges[idx]))
-            .collect::<Vec<(EdgeWeight, Edge)>>();
-        edges_by_weight.sort();
-
-        let n_edges = graph.neighbors.len() - 1;
-        let mut unions = 0;
-        for (_, Edge(src, dst)) in edges_by_weight.into_iter() {
-            if unions == n_edges {
-                break;
+        let n = edges.len();
+        if n == 0 {
+            return;
+        }
+        scratch.clear();
+        scratch.resize(n, (0, Edge(0, 0)));
+
+        let mut from_scratch = false;
+        for pass in 0..4 {
+            let shift = pass * 8;
+            let (src, dst): (&mut Vec<WeightedEdge>, &mut Vec<WeightedEdge>) =
+                if from_scratch {
+                    (scratch, edges)
+                } else {
+                    (edges, scratch)
+                };
+
+            let mut counts = [0usize; 257];
+            for &(w, _) in src.iter() {
+                counts[(((w >> shift) & 0xFF) as usize) + 1] += 1;
             }
-            if !ut.unioned(keys[src], keys[dst]) {
-                ut.union(keys[src], keys[dst]);
-                buf.st[src].push(dst);
-                buf.st[dst].push(src);
-                unions += 1;
+            for i in 0..256 {
+                counts[i + 1] += counts[i];
+            }
+            for &(w, e) in src.iter() {
+                let bucket = ((w >> shift) & 0xFF) as usize;
+                dst[counts[bucket]] = (w, e);
+                counts[bucket] += 1;
             }
+
+            from_scratch = !from_scratch;
         }
-        if unions != n_edges {
-            panic!(
-                "expected to have {} edges in MST but got {}",
-                n_edges, unions
-            );
+
+        if from_scratch {
+            // An odd number of passes would leave the sorted data in
+            // `scratch`; copy it back. (With 4 passes this never triggers.)
+            edges.copy_from_slice(scratch);
+        }
+    }
+
+    impl RMSTSampler {
+        /// Given `self.weights`, finds the minimum spanning forest of `graph`
+        /// using Kruskal's algorithm (run to exhaustion over all edges, rather
+        /// than stopping at `n - 1` unions) and inserts it into `buf`. Works
+        /// whether or not `graph` is connected: if it has `c` connected
+        /// components, the result is a forest of `n - c` edges, and the `c`
+        /// component roots are recorded in `buf.roots`.
+        ///
+        /// Reuses `self.ut`, `self.keys`, `self.edges_by_weight`, and
+        /// `self.radix_scratch` across calls instead of reallocating them.
+        fn minimum_spanning_tree(&mut self, graph: &Graph, buf: &mut SpanningTreeBuffer) {
+            buf.clear();
+
+            // Reset the union-find table in place rather than reallocating it.
+            let n_edges_total = graph.edges.len();
+            self.ut.reset_unifications(|_| ());
+            // Keys are indexed by node id (both here and in the
+            // component-root pass below), so the key buffer must cover every
+            // node -- not just every edge, which can undercount nodes for a
+            // sparse or disconnected graph.
+            let n = graph.pops.len();
+            if self.keys.len() < n {
+                let to_add = n - self.keys.len();
+                self.keys.reserve(to_add);
+                for _ in 0..to_add {
+                    self.keys.push(self.ut.new_key(()));
+                }
+            }
+
+            // Radix-sort edges by their (uniformly random) weight instead of
+            // using a comparison sort.
+            self.edges_by_weight.clear();
+            self.edges_by_weight.reserve(n_edges_total);
+            for (idx, &w) in self.weights.iter().enumerate() {
+                self.edges_by_weight.push((w, graph.edges[idx]));
+            }
+            radix_sort_by_weight(&mut self.edges_by_weight, &mut self.radix_scratch);
+
+            // Apply Kruskal's algorithm: union components in increasing order
+            // of edge weight until every edge has been considered (rather
+            // than stopping as soon as `n - 1` unions are made), so that a
+            // disconnected subgraph yields a minimum spanning forest instead
+            // of a partial tree.
+            for &(_, Edge(src, dst)) in self.edges_by_weight.iter() {
+                if !self.ut.unioned(self.keys[src], self.keys[dst]) {
+                    self.ut.union(self.keys[src], self.keys[dst]);
+                    buf.st[src].push(dst);
+                    buf.st[dst].push(src);
+                }
+            }
+
+            // Record one representative node per connected component.
+            buf.roots.clear();
+            if self.seen_roots.len() < n {
+                self.seen_roots.resize(n, false);
+            }
+            for seen in self.seen_roots[..n].iter_mut() {
+                *seen = false;
+            }
+            for node in 0..n {
+                let root = self.ut.find(self.keys[node]).index() as usize;
+                if !self.seen_roots[root] {
+                    self.seen_roots[root] = true;
+                    buf.roots.push(node);
+                }
+            }
         }
     }
 
     impl SpanningTreeSampler for RMSTSampler {
-        /// Draws a random spanning tree of a graph by sampling random edge weights
-        /// and finding the minimum spanning tree (using Kruskal's algorithm).
+        /// Draws a random spanning tree (or, if `graph` is disconnected, a random
+        /// spanning forest) of a graph by sampling random edge weights and finding
+        /// the minimum spanning tree (using Kruskal's algorithm).
         /// Returns nothing; The MST buffer `buf` is updated in place.
         ///
         /// # Arguments
         /// * `graph` - The graph to form a spanning tree from.
         /// * `buf` - The buffer to insert the spanning tree into.
         /// * `rng` - A random number generator (used to generate random edge weights).
-        fn random_spanning_tree(
+        fn random_spanning_tree<R: Rng>(
             &mut self,
             graph: &Graph,
             buf: &mut SpanningTreeBuffer,
-            rng: &mut SmallRng,
+            rng: &mut R,
         ) {
             // Sample edge weights uniformly at random and find the associated MST.
-            self.weights.reserve(graph.edges.len());
-            rng.fill(&mut self.weights[0..graph.edges.len()]);
-            minimum_spanning_tree(graph, buf, &self.weights);
-            self.weights.clear();
+            self.weights.resize(graph.edges.len(), 0);
+            rng.fill(&mut self.weights[..]);
+            self.minimum_spanning_tree(graph, buf);
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use rand::rngs::SmallRng;
+        use rand::{Rng, SeedableRng};
+
+        #[test]
+        fn radix_sort_matches_comparison_sort() {
+            let mut rng = SmallRng::seed_from_u64(1);
+            let mut weights = vec![0u32; 500];
+            rng.fill(&mut weights[..]);
+            let mut edges: Vec<WeightedEdge> = weights
+                .into_iter()
+                .enumerate()
+                .map(|(i, w)| (w, Edge(i, i + 1)))
+                .collect();
+            let mut expected = edges.clone();
+            expected.sort_by_key(|&(w, _)| w);
+
+            let mut scratch = Vec::new();
+            radix_sort_by_weight(&mut edges, &mut scratch);
+
+            let weights: Vec<EdgeWeight> = edges.iter().map(|&(w, _)| w).collect();
+            let expected_weights: Vec<EdgeWeight> = expected.iter().map(|&(w, _)| w).collect();
+            assert_eq!(weights, expected_weights);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    /// Two disconnected components: 0--1 and 2--3.
+    fn disconnected_graph() -> Graph {
+        Graph {
+            pops: vec![1, 1, 1, 1],
+            neighbors: vec![vec![1], vec![0], vec![3], vec![2]],
+            edges: vec![Edge(0, 1), Edge(2, 3)],
+            edges_start: vec![0, 1, 1, 2],
+        }
+    }
+
+    #[test]
+    fn ust_sampler_falls_back_to_spanning_forest() {
+        let graph = disconnected_graph();
+        let n = graph.pops.len();
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut sampler = USTSampler::new(n, &mut rng);
+        let mut buf = SpanningTreeBuffer::new(n);
+        sampler.random_spanning_tree(&graph, &mut buf, &mut rng);
+
+        // Two components -> two roots and n - 2 = 2 forest edges.
+        assert_eq!(buf.roots.len(), 2);
+        let total_edges: usize = buf.st.iter().map(|adj| adj.len()).sum::<usize>() / 2;
+        assert_eq!(total_edges, n - buf.roots.len());
+    }
+
+    #[test]
+    fn rmst_sampler_falls_back_to_spanning_forest() {
+        let graph = disconnected_graph();
+        let n = graph.pops.len();
+        let mut sampler = RMSTSampler::new(n);
+        let mut buf = SpanningTreeBuffer::new(n);
+        let mut rng = SmallRng::seed_from_u64(0);
+        sampler.random_spanning_tree(&graph, &mut buf, &mut rng);
+
+        assert_eq!(buf.roots.len(), 2);
+        let total_edges: usize = buf.st.iter().map(|adj| adj.len()).sum::<usize>() / 2;
+        assert_eq!(total_edges, n - buf.roots.len());
+    }
+
+    #[test]
+    fn weighted_ust_sampler_falls_back_to_spanning_forest() {
+        let graph = disconnected_graph();
+        let n = graph.pops.len();
+        let weights = vec![1.0; graph.edges.len()];
+        let mut sampler = WeightedUSTSampler::new(n);
+        let mut buf = SpanningTreeBuffer::new(n);
+        let mut rng = SmallRng::seed_from_u64(0);
+        sampler.random_spanning_tree(&graph, &weights, &mut buf, &mut rng);
+
+        assert_eq!(buf.roots.len(), 2);
+        let total_edges: usize = buf.st.iter().map(|adj| adj.len()).sum::<usize>() / 2;
+        assert_eq!(total_edges, n - buf.roots.len());
+    }
 }